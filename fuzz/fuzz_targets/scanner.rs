@@ -0,0 +1,74 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use veonep::{
+    minify::minify,
+    scanner::{Limits, Scanner},
+};
+
+const FLAGS: [&str; 3] = ["A", "B", "DEBUG"];
+
+fn lexemes(source: &str, flags: &[&str]) -> Option<Vec<String>> {
+    let tokens = Scanner::new(source.to_owned())
+        .with_flags(flags.iter().copied())
+        .tokenize()
+        .ok()?;
+    Some(
+        tokens
+            .tokens
+            .iter()
+            .map(|token| format!("{:?} {}", token.tty, token.lexeme(source)))
+            .collect(),
+    )
+}
+
+// The first four bytes pick the options and the mode, the rest is the
+// source. Any input may be rejected with an error, but the scanner must
+// never panic, and minified output must scan to the same tokens.
+fuzz_target!(|data: &[u8]| {
+    let [options, flag_bits, limit, mode, source @ ..] = data else {
+        return;
+    };
+    let Ok(source) = std::str::from_utf8(source) else {
+        return;
+    };
+    // Flags that are off send their `#if` branches through `skip_inactive`.
+    let flags: Vec<_> = FLAGS
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| flag_bits >> i & 1 == 1)
+        .map(|(_, flag)| *flag)
+        .collect();
+    let mut scanner = Scanner::new(source.to_owned())
+        .with_flags(flags.iter().copied())
+        .with_trivia(options & 1 == 1);
+    let limited = options & 2 == 2;
+    if limited {
+        let limit = *limit as usize;
+        scanner = scanner.with_limits(Limits {
+            max_tokens: limit * 4,
+            max_string_length: limit,
+            max_nesting: limit % 16,
+            max_errors: limit % 8 + 1,
+        });
+    }
+    if options & 4 == 4 {
+        // Includes versions the scanner does not support.
+        scanner = scanner.with_language_version(*limit as u32 % 3);
+    }
+    match mode % 3 {
+        0 => {
+            let _ = scanner.tokenize();
+        }
+        1 => {
+            let _ = scanner.tokenize_recovering();
+        }
+        _ => {
+            if let Ok(minified) = minify(scanner) {
+                if !limited && options & 4 == 0 {
+                    assert_eq!(lexemes(&minified, &flags), lexemes(source, &flags));
+                }
+            }
+        }
+    }
+});
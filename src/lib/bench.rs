@@ -0,0 +1,234 @@
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+use crate::{error::VeonError, scanner::Scanner};
+
+/// A representative Veon program bundled with the crate for benchmarking.
+#[derive(Debug, Clone, Copy)]
+pub struct BenchScript {
+    pub name: &'static str,
+    pub source: &'static str,
+}
+
+pub const SCRIPTS: &[BenchScript] = &[
+    BenchScript {
+        name: "fib",
+        source: r#"
+        fun fib(n) {
+            if (n < 2) {
+                return n;
+            }
+            return fib(n - 1) + fib(n - 2);
+        }
+        let i = 0;
+        while (i <= 25) {
+            print fib(i);
+            i = i + 1;
+        }
+        "#,
+    },
+    BenchScript {
+        name: "loops",
+        source: r#"
+        let total = 0;
+        let i = 0;
+        while (i < 100000) {
+            let j = 0;
+            while (j < 10) {
+                if (j % 2 == 0) {
+                    total = total + j * i;
+                } else {
+                    total = total - j / 2;
+                }
+                j = j + 1;
+            }
+            i = i + 1;
+        }
+        print total;
+        "#,
+    },
+    BenchScript {
+        name: "strings",
+        source: r#"
+        let out = "";
+        let i = 0;
+        while (i < 1000) {
+            out = out + "item" + "," + " ";
+            if (i % 10 == 0) {
+                out = out + "line";
+            }
+            i = i + 1;
+        }
+        print out;
+        "#,
+    },
+    BenchScript {
+        name: "methods",
+        source: r#"
+        class Counter {
+            init(start) {
+                this.count = start;
+            }
+            step(by) {
+                this.count = this.count + by;
+                return this;
+            }
+            get() {
+                return this.count;
+            }
+        }
+        let counter = Counter(0);
+        let i = 0;
+        while (i < 10000) {
+            counter.step(1).step(2).step(-3);
+            i = i + 1;
+        }
+        print counter.get();
+        "#,
+    },
+];
+
+/// How many untimed warmup runs and timed samples to take per script.
+#[derive(Debug, Clone, Copy)]
+pub struct BenchConfig {
+    pub warmup: usize,
+    pub iterations: usize,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        Self {
+            warmup: 3,
+            iterations: 20,
+        }
+    }
+}
+
+/// Timing samples collected for a single script.
+#[derive(Debug, Clone)]
+pub struct BenchReport {
+    pub name: &'static str,
+    /// The pipeline stage that was timed, e.g. `"scan"`.
+    pub stage: &'static str,
+    pub samples: Vec<Duration>,
+}
+
+impl BenchReport {
+    pub fn min(&self) -> Duration {
+        self.samples.iter().min().copied().unwrap_or_default()
+    }
+
+    pub fn max(&self) -> Duration {
+        self.samples.iter().max().copied().unwrap_or_default()
+    }
+
+    pub fn mean(&self) -> Duration {
+        if self.samples.is_empty() {
+            return Duration::ZERO;
+        }
+        self.samples.iter().sum::<Duration>() / self.samples.len() as u32
+    }
+
+    pub fn median(&self) -> Duration {
+        if self.samples.is_empty() {
+            return Duration::ZERO;
+        }
+        let mut sorted = self.samples.clone();
+        sorted.sort();
+        let mid = sorted.len() / 2;
+        if sorted.len().is_multiple_of(2) {
+            (sorted[mid - 1] + sorted[mid]) / 2
+        } else {
+            sorted[mid]
+        }
+    }
+
+    pub fn std_dev(&self) -> Duration {
+        if self.samples.len() < 2 {
+            return Duration::ZERO;
+        }
+        let mean = self.mean().as_secs_f64();
+        let variance = self
+            .samples
+            .iter()
+            .map(|s| (s.as_secs_f64() - mean).powi(2))
+            .sum::<f64>()
+            / (self.samples.len() - 1) as f64;
+        Duration::from_secs_f64(variance.sqrt())
+    }
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<10} stage {:<6} mean {:>12?}  median {:>12?}  min {:>12?}  max {:>12?}  stddev {:>12?}  ({} runs)",
+            self.name,
+            self.stage,
+            self.mean(),
+            self.median(),
+            self.min(),
+            self.max(),
+            self.std_dev(),
+            self.samples.len()
+        )
+    }
+}
+
+/// Runs a single script through the pipeline `config.warmup` times untimed,
+/// then `config.iterations` times timed.
+///
+/// Scanning is currently the only pipeline stage, so that is what each sample
+/// measures, and each report is labelled as a `"scan"` stage.
+pub fn run_script(script: &BenchScript, config: BenchConfig) -> Result<BenchReport, VeonError> {
+    measure(script.name, script.source, config)
+}
+
+/// Runs every bundled script in [`SCRIPTS`].
+pub fn run(config: BenchConfig) -> Result<Vec<BenchReport>, VeonError> {
+    SCRIPTS
+        .iter()
+        .map(|script| run_script(script, config))
+        .collect()
+}
+
+/// Builds a large source file by concatenating `copies` of every bundled
+/// script, for measuring raw scanner throughput.
+pub fn generate_script(copies: usize) -> String {
+    let mut source = String::new();
+    for _ in 0..copies {
+        for script in SCRIPTS {
+            source.push_str(script.source);
+        }
+    }
+    source
+}
+
+/// Times the scanner over [`generate_script`]`(copies)`.
+pub fn run_scanner(config: BenchConfig, copies: usize) -> Result<BenchReport, VeonError> {
+    measure("scanner", &generate_script(copies), config)
+}
+
+fn measure(
+    name: &'static str,
+    source: &str,
+    config: BenchConfig,
+) -> Result<BenchReport, VeonError> {
+    for _ in 0..config.warmup {
+        Scanner::new(source.to_owned()).tokenize()?;
+    }
+    let mut samples = Vec::with_capacity(config.iterations);
+    for _ in 0..config.iterations {
+        let mut scanner = Scanner::new(source.to_owned());
+        let start = Instant::now();
+        scanner.tokenize()?;
+        samples.push(start.elapsed());
+    }
+    Ok(BenchReport {
+        name,
+        stage: "scan",
+        samples,
+    })
+}
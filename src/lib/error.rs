@@ -1,30 +1,70 @@
 use strum::Display;
 
+use crate::{source::FileId, token::Span};
+
 #[derive(thiserror::Error, Debug)]
 pub enum VeonError {
     #[error("Error while parsing: {0}")]
     ParserError(#[from] ParserError),
     #[error("Error while scanning: {0}")]
-    ScannerError(ScannerError),
+    ScannerError(#[from] ScannerError),
     #[error("Error while evaluating: {0}")]
     EvaluationError(EvaluationError),
 }
 
+impl VeonError {
+    /// The stable diagnostic code, e.g. `V0002`. See `veonep explain <code>`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            VeonError::ParserError(e) => e.tty.code(),
+            VeonError::ScannerError(e) => e.tty.code(),
+            VeonError::EvaluationError(e) => e.tty.code(),
+        }
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
-#[error("[{tty}:{msg}]")]
+#[error("[{}:{tty}:{msg}]", .tty.code())]
 pub struct ParserError {
     pub msg: String,
     pub tty: ParserErrorType,
 }
 #[derive(Debug, thiserror::Error)]
-#[error("[{tty}:{msg}]")]
+#[error("[{}:{tty}:{msg}]", .tty.code())]
 pub struct ScannerError {
     pub msg: String,
     pub tty: ScannerErrorType,
+    pub file: FileId,
+    pub span: Span,
+    pub suggestion: Option<Suggestion>,
+}
+
+impl ScannerError {
+    pub fn with_suggestion(mut self, suggestion: Suggestion) -> Self {
+        self.suggestion = Some(suggestion);
+        self
+    }
+}
+
+/// A machine-applicable fix attached to a diagnostic: replacing `span` with
+/// `replacement` resolves the error. An empty span is an insertion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    pub msg: String,
+    pub span: Span,
+    pub replacement: String,
+}
+
+impl Suggestion {
+    pub fn apply(&self, source: &str) -> String {
+        let mut fixed = source.to_owned();
+        fixed.replace_range(self.span.range(), &self.replacement);
+        fixed
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
-#[error("[{tty}:{msg}]")]
+#[error("[{}:{tty}:{msg}]", .tty.code())]
 pub struct EvaluationError {
     pub msg: String,
     pub tty: EvaluationErrorType,
@@ -35,10 +75,25 @@ pub enum ParserErrorType {
     InvalidExpression,
 }
 
-#[derive(Debug, Display)]
+#[derive(Debug, Display, Clone, Copy, PartialEq, Eq)]
 
 pub enum ScannerErrorType {
     InvalidToken,
+    UnterminatedString,
+    InvalidNumber,
+    UnterminatedDirective,
+    UnknownDirective,
+    UnmatchedDirective,
+    MissingFlag,
+    UnsupportedVersion,
+    MisplacedVersion,
+    InvalidEscape,
+    TooManyTokens,
+    StringTooLong,
+    NestingTooDeep,
+    UnterminatedComment,
+    MisplacedDirective,
+    HiddenDirective,
 }
 
 #[derive(Debug, Display)]
@@ -47,3 +102,44 @@ pub enum EvaluationErrorType {
     InvalidOperation,
     InvalidTypeOperation,
 }
+
+impl ScannerErrorType {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ScannerErrorType::InvalidToken => "V0001",
+            ScannerErrorType::UnterminatedString => "V0002",
+            ScannerErrorType::InvalidNumber => "V0003",
+            ScannerErrorType::UnterminatedDirective => "V0004",
+            ScannerErrorType::UnknownDirective => "V0005",
+            ScannerErrorType::UnmatchedDirective => "V0006",
+            ScannerErrorType::MissingFlag => "V0007",
+            ScannerErrorType::UnsupportedVersion => "V0008",
+            ScannerErrorType::MisplacedVersion => "V0009",
+            ScannerErrorType::InvalidEscape => "V0010",
+            ScannerErrorType::TooManyTokens => "V0011",
+            ScannerErrorType::StringTooLong => "V0012",
+            ScannerErrorType::NestingTooDeep => "V0013",
+            ScannerErrorType::UnterminatedComment => "V0014",
+            ScannerErrorType::MisplacedDirective => "V0015",
+            ScannerErrorType::HiddenDirective => "V0016",
+        }
+    }
+}
+
+impl ParserErrorType {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParserErrorType::InvalidExpression => "V0101",
+        }
+    }
+}
+
+impl EvaluationErrorType {
+    pub fn code(&self) -> &'static str {
+        match self {
+            EvaluationErrorType::DivideByZero => "V0201",
+            EvaluationErrorType::InvalidOperation => "V0202",
+            EvaluationErrorType::InvalidTypeOperation => "V0203",
+        }
+    }
+}
@@ -0,0 +1,331 @@
+/// Long-form description of a diagnostic code, shown by `veonep explain`.
+#[derive(Debug, Clone, Copy)]
+pub struct Explanation {
+    pub code: &'static str,
+    pub title: &'static str,
+    pub text: &'static str,
+}
+
+pub const EXPLANATIONS: &[Explanation] = &[
+    Explanation {
+        code: "V0001",
+        title: "invalid token",
+        text: r#"The scanner found a character that does not start any Veon token.
+
+Erroneous example:
+
+    let total = price @ 2;
+
+`@` is not an operator in Veon. Remove the stray character or replace it
+with the intended operator:
+
+    let total = price * 2;
+"#,
+    },
+    Explanation {
+        code: "V0002",
+        title: "unterminated string",
+        text: r#"A string literal was opened with `"` but the file ended before the
+closing quote, or an embedded `${...}` expression was never closed.
+
+Erroneous example:
+
+    let greeting = "hello;
+
+Add the missing quote:
+
+    let greeting = "hello";
+
+The error points at the line where the string started, since the end of
+the file is rarely where the mistake is.
+"#,
+    },
+    Explanation {
+        code: "V0003",
+        title: "invalid number literal",
+        text: r#"A number literal could not be represented.
+
+Erroneous example:
+
+    let big = 99999999999999999999999;
+
+Integer literals must fit in the platform's native integer size, and
+float literals must be finite. Use a smaller number, or write it with a
+fractional part (`99999999999999999999999.0`) if a float is acceptable.
+"#,
+    },
+    Explanation {
+        code: "V0004",
+        title: "unterminated #if",
+        text: r#"An `#if` directive was opened but the file ended before its `#end`.
+
+Erroneous example:
+
+    #if DEBUG
+    print "debugging";
+
+Close every `#if` with `#end`:
+
+    #if DEBUG
+    print "debugging";
+    #end
+"#,
+    },
+    Explanation {
+        code: "V0005",
+        title: "unknown directive",
+        text: r#"A `#` was followed by something other than `version`, `if`, `else` or
+`end`.
+
+Erroneous example:
+
+    #ifdef DEBUG
+
+Use `#version N`, `#if FLAG`, `#else` and `#end`.
+"#,
+    },
+    Explanation {
+        code: "V0006",
+        title: "unmatched directive",
+        text: r#"An `#else` or `#end` appeared without an open `#if`, or an `#if` has
+more than one `#else`.
+
+Erroneous example:
+
+    print "always";
+    #end
+
+Remove the stray directive, or add the `#if` it was meant to close.
+"#,
+    },
+    Explanation {
+        code: "V0007",
+        title: "missing #if flag",
+        text: r#"An `#if` directive must name the flag it tests.
+
+Erroneous example:
+
+    #if
+    print "debugging";
+    #end
+
+Name the flag the host enables:
+
+    #if DEBUG
+"#,
+    },
+    Explanation {
+        code: "V0008",
+        title: "unsupported language version",
+        text: r#"A `#version` pragma, or the host embedding Veon, asked for a language
+version this build does not implement, or the pragma was not followed by
+a number.
+
+Erroneous example:
+
+    #version 7
+
+Use a version in the supported range, or upgrade the interpreter. Scripts
+without a pragma use the version chosen by the host, which defaults to
+the newest.
+"#,
+    },
+    Explanation {
+        code: "V0009",
+        title: "misplaced #version",
+        text: r#"A `#version` pragma selects the rules for the whole file, so it must
+appear once, before any code. Comments and blank lines may precede it.
+
+Erroneous example:
+
+    let a = 1;
+    #version 1
+
+Move the pragma to the top of the file:
+
+    #version 1
+    let a = 1;
+"#,
+    },
+    Explanation {
+        code: "V0010",
+        title: "invalid escape sequence",
+        text: r#"A backslash in a string literal was not followed by a recognised escape.
+
+Erroneous example:
+
+    let path = "C:\data\files";
+
+The supported escapes are `\n`, `\t`, `\r`, `\0`, `\"`, `\$`, `\\` and
+`\u{XXXX}` (one to six hex digits naming a Unicode scalar value). Write
+`\\` for a literal backslash:
+
+    let path = "C:\\data\\files";
+"#,
+    },
+    Explanation {
+        code: "V0011",
+        title: "too many tokens",
+        text: r#"The source produced more tokens than the host allows. This limit is only
+enforced when the embedding application sets one, typically because it
+accepts scripts from untrusted users.
+
+Split the script into smaller files, or ask the host to raise the limit.
+Scanning stops at the first token over the limit.
+"#,
+    },
+    Explanation {
+        code: "V0012",
+        title: "string literal too long",
+        text: r#"A string literal held more bytes than the host allows. The length is
+counted after escapes are decoded, and each segment of an interpolated
+string is counted on its own.
+
+Load large text from a file or build it from smaller pieces instead of
+writing it as one literal.
+"#,
+    },
+    Explanation {
+        code: "V0013",
+        title: "nesting too deep",
+        text: r#"Parentheses, brackets, braces and `${` interpolations were nested more
+deeply than the host allows.
+
+Erroneous example, with a limit of 2:
+
+    let x = ((((1))));
+
+Flatten the expression, for example by naming intermediate results:
+
+    let inner = (1);
+    let x = (inner);
+
+Scanning stops at the first bracket over the limit.
+"#,
+    },
+    Explanation {
+        code: "V0014",
+        title: "unterminated block comment",
+        text: r#"A `/*` comment was opened but the file ended before its closing `*/`.
+Block comments nest, so every `/*` inside a comment needs its own `*/`.
+
+Erroneous example:
+
+    /* disabled:
+    /* old helper */
+    fun helper() {}
+
+Close each comment that was opened:
+
+    /* disabled:
+    /* old helper */
+    fun helper() {}
+    */
+"#,
+    },
+    Explanation {
+        code: "V0015",
+        title: "directive not on a line of its own",
+        text: r#"`#if`, `#else`, `#end` and `#version` must each start a line and be the
+only thing on it. Whitespace and a trailing `//` comment are allowed.
+Whether a flag is set decides which lines are skipped, so a directive
+sharing a line with code would mean different things in each branch.
+
+Erroneous example:
+
+    #if DEBUG log(x) #end
+    run();
+
+Put each directive on its own line:
+
+    #if DEBUG
+    log(x)
+    #end
+    run();
+"#,
+    },
+    Explanation {
+        code: "V0016",
+        title: "directive inside a string or comment",
+        text: r#"Inside an `#if` branch, a line of a multi-line string or block comment
+starts with `#if`, `#else` or `#end`. A branch that is not taken is
+skipped line by line without reading strings or comments, so that line
+would act as a directive when the flag is off but as text when it is on.
+It is rejected in both cases.
+
+Erroneous example:
+
+    #if DEBUG
+    let banner = "build:
+    #else
+    release";
+    #end
+
+Indent the text differently or build it from escapes, so no line of it
+starts with a directive:
+
+    #if DEBUG
+    let banner = "build:\n#else\nrelease";
+    #end
+"#,
+    },
+    Explanation {
+        code: "V0101",
+        title: "invalid expression",
+        text: r#"The parser expected an expression but found something else.
+
+Erroneous example:
+
+    let x = * 2;
+
+Make sure every operator has operands on both sides:
+
+    let x = 3 * 2;
+"#,
+    },
+    Explanation {
+        code: "V0201",
+        title: "division by zero",
+        text: r#"A number was divided by zero.
+
+Erroneous example:
+
+    let ratio = hits / attempts;    // attempts == 0
+
+Check the divisor before dividing:
+
+    if (attempts != 0) { ratio = hits / attempts; }
+"#,
+    },
+    Explanation {
+        code: "V0202",
+        title: "invalid operation",
+        text: r#"An operator was applied in a way Veon does not support, such as negating
+a string.
+
+Erroneous example:
+
+    let x = -"ten";
+
+Convert the operand to a supported type first.
+"#,
+    },
+    Explanation {
+        code: "V0203",
+        title: "invalid operand types",
+        text: r#"A binary operator was given operands of incompatible types.
+
+Erroneous example:
+
+    let x = "total: " - 5;
+
+Use operands of matching types, e.g. `+` for string concatenation.
+"#,
+    },
+];
+
+pub fn explain(code: &str) -> Option<&'static Explanation> {
+    EXPLANATIONS
+        .iter()
+        .find(|explanation| explanation.code.eq_ignore_ascii_case(code))
+}
@@ -0,0 +1,91 @@
+use std::{collections::HashMap, fmt::Display};
+
+/// Built-in message templates, keyed by diagnostic code. `{0}`, `{1}`, ...
+/// are replaced with the arguments supplied where the error is raised.
+/// Suggestion text is keyed by the code plus a suffix, e.g. `V0002-close`.
+pub const DEFAULT_MESSAGES: &[(&str, &str)] = &[
+    ("V0001", "Unexpected character '{0}' on line {1}"),
+    ("V0002", "Unterminated string starting on line {0}"),
+    ("V0003", "Number literal '{0}' is out of range on line {1}"),
+    ("V0004", "'#if' on line {0} is never closed with '#end'"),
+    ("V0005", "Unknown directive '#{0}' on line {1}"),
+    ("V0006", "'#{0}' on line {1} has no matching '#if'"),
+    ("V0007", "'#if' on line {0} needs a flag name"),
+    (
+        "V0008",
+        "Language version '{0}' on line {1} is not supported (expected {2} to {3})",
+    ),
+    (
+        "V0009",
+        "'#version' on line {0} must come once, before any code",
+    ),
+    ("V0010", "Invalid escape sequence '{0}' on line {1}"),
+    (
+        "V0011",
+        "Source exceeds the limit of {0} tokens on line {1}",
+    ),
+    (
+        "V0012",
+        "String starting on line {0} is longer than {1} bytes",
+    ),
+    ("V0013", "Nesting deeper than {0} levels on line {1}"),
+    ("V0014", "Unterminated block comment starting on line {0}"),
+    ("V0015", "'#{0}' on line {1} must be on a line of its own"),
+    (
+        "V0016",
+        "'#{0}' on line {1} is inside a string or comment in an '#if' branch",
+    ),
+    ("V0001-quote", "strings use double quotes"),
+    ("V0001-and", "use 'and' for logical and"),
+    ("V0001-or", "use 'or' for logical or"),
+    ("V0002-close", "close the string"),
+    ("V0002-interpolation", "close the interpolation"),
+    ("V0005-rename", "did you mean '#{0}'?"),
+    (
+        "V0008-host",
+        "Language version '{0}' set by the host is not supported (expected {1} to {2})",
+    ),
+    ("V0010-backslash", "write '\\\\' for a literal backslash"),
+    ("V0014-close", "close the comment"),
+    ("V0015-newline", "start a new line here"),
+];
+
+/// Diagnostic messages, with optional per-code overrides so embedders can
+/// translate or rephrase them without touching the error kinds or codes.
+#[derive(Debug, Clone, Default)]
+pub struct MessageCatalog {
+    overrides: HashMap<String, String>,
+}
+
+impl MessageCatalog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the template for `code`. The template may use the same
+    /// positional placeholders as the default one.
+    pub fn set(&mut self, code: &str, template: impl Into<String>) -> &mut Self {
+        self.overrides.insert(code.to_owned(), template.into());
+        self
+    }
+
+    pub fn template(&self, code: &str) -> Option<&str> {
+        self.overrides.get(code).map(String::as_str).or_else(|| {
+            DEFAULT_MESSAGES
+                .iter()
+                .find(|(default, _)| *default == code)
+                .map(|(_, template)| *template)
+        })
+    }
+
+    pub fn format(&self, code: &str, args: &[&dyn Display]) -> String {
+        let Some(template) = self.template(code) else {
+            return code.to_owned();
+        };
+        let mut msg = template.to_owned();
+        for (i, arg) in args.iter().enumerate() {
+            msg = msg.replace(&format!("{{{}}}", i), &arg.to_string());
+        }
+        msg
+    }
+}
@@ -0,0 +1,73 @@
+use crate::{
+    error::VeonError,
+    scanner::Scanner,
+    token::{Token, TokenType, TriviaKind},
+};
+
+/// Rewrites a source with comments and redundant whitespace removed.
+///
+/// Directives are kept on lines of their own and branches that were
+/// skipped by `#if` are copied unchanged, so the result still responds to
+/// the same flags. Tokens are only separated where joining them would
+/// scan differently, e.g. `let x` or `a = =b`.
+pub fn minify(scanner: Scanner) -> Result<String, VeonError> {
+    let mut scanner = scanner.with_trivia(true);
+    let tokens = scanner.tokenize()?;
+    let source = scanner.source.as_str();
+    let mut out = String::new();
+    let mut previous: Option<&Token> = None;
+    // A directive ends the line it is on; the next token starts a new one.
+    let mut line_open = false;
+    for index in 0..=tokens.tokens.len() {
+        for trivia in tokens.leading_trivia(index) {
+            let text = &source[trivia.span.range()];
+            match trivia.kind {
+                TriviaKind::Whitespace | TriviaKind::LineComment | TriviaKind::BlockComment => {}
+                TriviaKind::Directive => {
+                    let line = &out[out.rfind('\n').map_or(0, |i| i + 1)..];
+                    if !line.trim().is_empty() {
+                        out.push('\n');
+                    }
+                    out.push_str(text);
+                    line_open = true;
+                    previous = None;
+                }
+                TriviaKind::Skipped => {
+                    // A skipped branch starts with the rest of the directive
+                    // line and runs up to the directive that ends it.
+                    out.push_str(text);
+                    line_open = false;
+                    previous = None;
+                }
+            }
+        }
+        let Some(token) = tokens.tokens.get(index) else {
+            break;
+        };
+        if line_open {
+            out.push('\n');
+            line_open = false;
+        } else if previous.is_some_and(|previous| needs_space(previous, token, source)) {
+            out.push(' ');
+        }
+        out.push_str(token.lexeme(source));
+        previous = Some(token);
+    }
+    Ok(out)
+}
+
+/// Whether `left` and `right` would scan as different tokens if written
+/// with nothing between them.
+fn needs_space(left: &Token, right: &Token, source: &str) -> bool {
+    let left_text = left.lexeme(source);
+    let right_text = right.lexeme(source);
+    let (Some(last), Some(first)) = (left_text.chars().last(), right_text.chars().next()) else {
+        return false;
+    };
+    let word = |c: char| c.is_alphanumeric() || c == '_';
+    (word(last) && word(first))
+        || ((left.tty == TokenType::Number || last == '.') && first == '.')
+        || (matches!(left_text, "=" | "<" | ">" | "!") && first == '=')
+        || (last == '/' && matches!(first, '/' | '*'))
+        || (left.tty == TokenType::QuestionMark && first == '.')
+}
@@ -1,3 +1,8 @@
+pub mod bench;
 pub mod scanner;
+pub mod source;
 pub mod error;
+pub mod explain;
+pub mod messages;
+pub mod minify;
 pub mod token;
\ No newline at end of file
@@ -1,16 +1,80 @@
-use substring::Substring;
+use std::{collections::HashSet, fmt::Display, sync::Arc};
 
 use crate::{
-    error::VeonError,
-    token::{Token, TokenType, Value},
+    error::{ScannerError, ScannerErrorType, Suggestion, VeonError},
+    messages::MessageCatalog,
+    source::FileId,
+    token::{Span, Token, TokenType, Tokens, Trivia, TriviaKind, Value, KEYWORDS},
 };
 
+/// The newest language version this crate implements; sources without a
+/// `#version` pragma are scanned as this version unless the host says
+/// otherwise.
+pub const LATEST_LANGUAGE_VERSION: u32 = 1;
+/// The oldest language version still accepted in a `#version` pragma.
+pub const OLDEST_LANGUAGE_VERSION: u32 = 1;
+
+/// Caps on what a single source may contain, for hosts that scan untrusted
+/// input. Every limit defaults to unlimited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// The most tokens a source may produce.
+    pub max_tokens: usize,
+    /// The most bytes a string literal, or one segment of an interpolated
+    /// string, may hold once escapes are decoded.
+    pub max_string_length: usize,
+    /// How deeply `(`, `[`, `{` and `${` may nest.
+    pub max_nesting: usize,
+    /// The most errors [`Scanner::tokenize_recovering`] collects before it
+    /// gives up on the rest of the source.
+    pub max_errors: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_tokens: usize::MAX,
+            max_string_length: usize::MAX,
+            max_nesting: usize::MAX,
+            max_errors: usize::MAX,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Scanner {
     pub source: String,
     start: usize,
     current: usize,
     line: usize,
+    literals: Vec<Value>,
+    catalog: Arc<MessageCatalog>,
+    file: FileId,
+    flags: HashSet<String>,
+    conditions: Vec<Condition>,
+    interpolations: Vec<Interpolation>,
+    limits: Limits,
+    depth: usize,
+    retain_trivia: bool,
+    trivia: Vec<Trivia>,
+    language_version: u32,
+    version_pragma: bool,
+}
+
+/// A `${` inside a string whose closing `}` has not been reached yet.
+#[derive(Debug, Clone)]
+struct Interpolation {
+    line: usize,
+    span: Span,
+    braces: usize,
+}
+
+/// An `#if` whose `#end` has not been reached yet.
+#[derive(Debug, Clone)]
+struct Condition {
+    line: usize,
+    span: Span,
+    in_else: bool,
 }
 
 impl Scanner {
@@ -20,125 +84,1028 @@ impl Scanner {
             start: 0,
             current: 0,
             line: 1,
+            literals: vec![],
+            catalog: Arc::default(),
+            file: FileId::default(),
+            flags: HashSet::new(),
+            conditions: vec![],
+            interpolations: vec![],
+            limits: Limits::default(),
+            depth: 0,
+            retain_trivia: false,
+            trivia: vec![],
+            language_version: LATEST_LANGUAGE_VERSION,
+            version_pragma: false,
+        }
+    }
+
+    /// Tags tokens and diagnostics with `file`, as registered in a
+    /// [`SourceMap`](crate::source::SourceMap).
+    pub fn with_file(mut self, file: FileId) -> Self {
+        self.file = file;
+        self
+    }
+
+    /// Uses `catalog` for the text of every diagnostic this scanner reports.
+    pub fn with_catalog(mut self, catalog: Arc<MessageCatalog>) -> Self {
+        self.catalog = catalog;
+        self
+    }
+
+    /// Sets the flags that `#if FLAG` directives test against.
+    pub fn with_flags<I, S>(mut self, flags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.flags = flags.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Keeps whitespace, comments and directives in [`Tokens::trivia`] instead
+    /// of discarding them, so tools can reproduce the source exactly.
+    pub fn with_trivia(mut self, retain_trivia: bool) -> Self {
+        self.retain_trivia = retain_trivia;
+        self
+    }
+
+    /// The language version to assume when the source has no `#version`
+    /// pragma, so hosts can pin existing scripts to the semantics they were
+    /// written for. A version outside the supported range is reported as
+    /// `UnsupportedVersion` when scanning starts, before any token.
+    pub fn with_language_version(mut self, version: u32) -> Self {
+        self.language_version = version;
+        self
+    }
+
+    /// Enforces `limits` while scanning. Exceeding the token or nesting limit
+    /// stops the scan, even in [`tokenize_recovering`](Self::tokenize_recovering),
+    /// which also stops once it has collected `max_errors` errors.
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "scan", level = "debug", skip_all, fields(bytes = self.source.len()))
+    )]
+    pub fn tokenize(&mut self) -> Result<Tokens, VeonError> {
+        self.check_language_version()?;
+        let mut tokens: Vec<Token> = vec![];
+        while !self.is_at_end() {
+            self.scan_token(&mut tokens)?;
+            self.check_token_count(&tokens)?;
+        }
+        if let Some(e) = self
+            .unclosed_interpolation()
+            .or_else(|| self.unclosed_condition())
+        {
+            return Err(e.into());
         }
+        Ok(self.finish(tokens))
     }
-    pub fn tokenize(&mut self) -> Result<Vec<Token>, VeonError> {
+
+    /// Scans the whole source even if it contains errors. Each lexical error
+    /// is recorded and the offending input skipped, so callers such as editors
+    /// get every token that could be recognised plus all diagnostics at once.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "scan", level = "debug", skip_all, fields(bytes = self.source.len()))
+    )]
+    pub fn tokenize_recovering(&mut self) -> (Tokens, Vec<ScannerError>) {
         let mut tokens: Vec<Token> = vec![];
+        let mut errors = vec![];
+        if let Err(e) = self.check_language_version() {
+            errors.push(e);
+            return (self.finish(tokens), errors);
+        }
         while !self.is_at_end() {
-            self.start = self.current;
-            let c = self.next();
-            match c {
-                '+' => tokens.push(Token {
-                    tty: TokenType::Plus,
-                    value: Value::None,
-                    line: self.line,
-                }),
-                '-' => tokens.push(Token {
-                    tty: TokenType::Minus,
-                    value: Value::None,
-                    line: self.line,
-                }),
-                '*' => tokens.push(Token {
-                    tty: TokenType::Star,
-                    value: Value::None,
-                    line: self.line,
-                }),
-                '/' => tokens.push(Token {
-                    tty: TokenType::Slash,
-                    value: Value::None,
-                    line: self.line,
-                }),
-                '?' => tokens.push(Token {
-                    tty: TokenType::QuestionMark,
-                    value: Value::None,
-                    line: self.line,
-                }),
-                '>' => {
-                    if self.next() == '=' {
-                        tokens.push(Token {
-                            tty: TokenType::GreaterEqual,
-                            value: Value::None,
-                            line: self.line,
-                        })
-                    } else {
-                        tokens.push(Token {
-                            tty: TokenType::Equal,
-                            value: Value::None,
-                            line: self.line,
-                        })
+            let result = self
+                .scan_token(&mut tokens)
+                .and_then(|()| self.check_token_count(&tokens));
+            if let Err(e) = result {
+                if matches!(
+                    e.tty,
+                    ScannerErrorType::TooManyTokens | ScannerErrorType::NestingTooDeep
+                ) {
+                    errors.push(e);
+                    return (self.finish(tokens), errors);
+                }
+                #[cfg(feature = "tracing")]
+                tracing::debug!(
+                    code = e.tty.code(),
+                    line = self.line,
+                    "recovered from scan error"
+                );
+                errors.push(e);
+                if errors.len() >= self.limits.max_errors {
+                    return (self.finish(tokens), errors);
+                }
+            }
+        }
+        while let Some(e) = self.unclosed_interpolation() {
+            errors.push(e);
+        }
+        while let Some(e) = self.unclosed_condition() {
+            errors.push(e);
+        }
+        errors.truncate(self.limits.max_errors);
+        (self.finish(tokens), errors)
+    }
+
+    fn scan_token(&mut self, tokens: &mut Vec<Token>) -> Result<(), ScannerError> {
+        self.start = self.current;
+        let c = self.advance();
+        match c {
+            '(' => {
+                self.open()?;
+                tokens.push(self.make_token(TokenType::LeftParen))
+            }
+            ')' => {
+                self.close();
+                tokens.push(self.make_token(TokenType::RightParen))
+            }
+            '{' => {
+                self.open()?;
+                if let Some(interpolation) = self.interpolations.last_mut() {
+                    interpolation.braces += 1;
+                }
+                tokens.push(self.make_token(TokenType::LeftBrace))
+            }
+            '}' => {
+                self.close();
+                match self.interpolations.last_mut() {
+                    Some(interpolation) if interpolation.braces == 0 => {
+                        let line = interpolation.line;
+                        self.interpolations.pop();
+                        tokens.push(self.string_segment(line)?)
                     }
+                    Some(interpolation) => {
+                        interpolation.braces -= 1;
+                        tokens.push(self.make_token(TokenType::RightBrace))
+                    }
+                    None => tokens.push(self.make_token(TokenType::RightBrace)),
                 }
-                '<' => {
-                    if self.next() == '=' {
-                        tokens.push(Token {
-                            tty: TokenType::LessEqual,
-                            value: Value::None,
-                            line: self.line,
-                        })
-                    } else {
-                        tokens.push(Token {
-                            tty: TokenType::Less,
-                            value: Value::None,
-                            line: self.line,
-                        })
+            }
+            '[' => {
+                self.open()?;
+                tokens.push(self.make_token(TokenType::LeftBracket))
+            }
+            ']' => {
+                self.close();
+                tokens.push(self.make_token(TokenType::RightBracket))
+            }
+            ',' => tokens.push(self.make_token(TokenType::Comma)),
+            '.' => {
+                let tty = if self.peek() == '.' && self.peek_next() == '.' {
+                    self.advance();
+                    self.advance();
+                    TokenType::Ellipsis
+                } else {
+                    TokenType::Dot
+                };
+                tokens.push(self.make_token(tty))
+            }
+            ';' => tokens.push(self.make_token(TokenType::Semicolon)),
+            '+' => tokens.push(self.make_token(TokenType::Plus)),
+            '-' => tokens.push(self.make_token(TokenType::Minus)),
+            '*' => tokens.push(self.make_token(TokenType::Star)),
+            '/' => {
+                if self.match_next('/') {
+                    while !self.is_at_end() && self.peek() != '\n' {
+                        self.advance();
                     }
+                    self.push_trivia(TriviaKind::LineComment, self.span());
+                } else if self.match_next('*') {
+                    self.block_comment()?;
+                } else {
+                    tokens.push(self.make_token(TokenType::Slash))
+                }
+            }
+            '%' => tokens.push(self.make_token(TokenType::Modulo)),
+            '?' => {
+                let tty = if self.match_next('.') {
+                    TokenType::QuestionDot
+                } else {
+                    TokenType::QuestionMark
+                };
+                tokens.push(self.make_token(tty))
+            }
+            '>' => {
+                let tty = if self.match_next('=') {
+                    TokenType::GreaterEqual
+                } else {
+                    TokenType::Greater
+                };
+                tokens.push(self.make_token(tty))
+            }
+            '<' => {
+                let tty = if self.match_next('=') {
+                    TokenType::LessEqual
+                } else {
+                    TokenType::Less
+                };
+                tokens.push(self.make_token(tty))
+            }
+            '=' => {
+                let tty = if self.match_next('=') {
+                    TokenType::EqualEqual
+                } else {
+                    TokenType::Equal
+                };
+                tokens.push(self.make_token(tty))
+            }
+            '!' => {
+                let tty = if self.match_next('=') {
+                    TokenType::NotEqual
+                } else {
+                    TokenType::Not
+                };
+                tokens.push(self.make_token(tty))
+            }
+            '"' => tokens.push(self.tokenize_string()?),
+            '#' => self.directive(tokens.is_empty())?,
+            '\n' => {
+                self.line += 1;
+                self.push_trivia(TriviaKind::Whitespace, self.span());
+            }
+            ' ' | '\t' | '\r' => self.push_trivia(TriviaKind::Whitespace, self.span()),
+            c if c.is_ascii_digit() => tokens.push(self.tokenize_number()?),
+            c if c.is_alphabetic() || c == '_' => tokens.push(self.tokenize_identifier()),
+            '\'' => {
+                let rest = &self.source[self.current..];
+                let line = &rest[..rest.find('\n').unwrap_or(rest.len())];
+                // Without a closing quote, treat the rest of the line as the
+                // string so the fix still yields a terminated literal.
+                let closing = line.find('\'');
+                let (text, quoted_end) = match closing {
+                    Some(end) => (&line[..end], self.current + end + 1),
+                    None => (line.trim_end(), self.current + line.trim_end().len()),
+                };
+                let replacement = format!(
+                    "\"{}\"",
+                    text.replace('\\', "\\\\")
+                        .replace('"', "\\\"")
+                        .replace("${", "\\${")
+                );
+                let fix = Suggestion {
+                    msg: self.catalog.format("V0001-quote", &[]),
+                    span: Span {
+                        start: self.start as u32,
+                        end: quoted_end as u32,
+                    },
+                    replacement,
+                };
+                if closing.is_some() {
+                    self.current = quoted_end;
+                }
+                let error = self.error(ScannerErrorType::InvalidToken, &[&c, &self.line]);
+                return Err(error.with_suggestion(fix));
+            }
+            '&' | '|' => {
+                self.match_next(c);
+                let (key, word) = if c == '&' {
+                    ("V0001-and", "and")
+                } else {
+                    ("V0001-or", "or")
+                };
+                // Keep the keyword apart from the operands, e.g. `a&&b`.
+                let word_char = |c: char| c.is_alphanumeric() || c == '_';
+                let before = self.source[..self.start].chars().next_back();
+                let mut replacement = String::new();
+                if before.is_some_and(word_char) {
+                    replacement.push(' ');
+                }
+                replacement.push_str(word);
+                if word_char(self.peek()) {
+                    replacement.push(' ');
+                }
+                let error = self.error(ScannerErrorType::InvalidToken, &[&c, &self.line]);
+                return Err(error.with_suggestion(self.suggest(key, &[], &replacement)));
+            }
+            c => return Err(self.error(ScannerErrorType::InvalidToken, &[&c, &self.line])),
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, tokens: Vec<Token>) -> Tokens {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            tokens = tokens.len(),
+            literals = self.literals.len(),
+            "scan finished"
+        );
+        Tokens {
+            file: self.file,
+            tokens,
+            literals: std::mem::take(&mut self.literals),
+            trivia: std::mem::take(&mut self.trivia),
+            language_version: self.language_version,
+        }
+    }
+
+    /// Handles `#version N`, `#if FLAG`, `#else` and `#end`. A branch whose
+    /// condition does not hold is skipped line by line and produces no tokens
+    /// at all, so disabled code costs nothing after scanning.
+    ///
+    /// A directive must be alone on its line, apart from whitespace and a
+    /// trailing `//` comment. Skipped branches are only searched for
+    /// directives at the start of a line, so the same rule holds whether or
+    /// not a flag is set.
+    fn directive(&mut self, before_code: bool) -> Result<(), ScannerError> {
+        let name = self.directive_name();
+        let known = matches!(name.as_str(), "version" | "if" | "else" | "end");
+        if known && !self.at_line_start() {
+            let error = self.error(ScannerErrorType::MisplacedDirective, &[&name, &self.line]);
+            return Err(error.with_suggestion(self.line_break_at(self.start)));
+        }
+        match name.as_str() {
+            "version" => {
+                if !before_code || self.version_pragma {
+                    return Err(self.error(ScannerErrorType::MisplacedVersion, &[&self.line]));
+                }
+                while matches!(self.peek(), ' ' | '\t') {
+                    self.advance();
+                }
+                // Up to the end of the word, so `1.5` is reported whole
+                // rather than as trailing text after `1`.
+                let start = self.current;
+                while !matches!(self.peek(), ' ' | '\t' | '\r' | '\n') && !self.is_at_end() {
+                    self.advance();
                 }
-                '=' => {
-                    if self.next() == '=' {
-                        tokens.push(Token {
-                            tty: TokenType::EqualEqual,
-                            value: Value::None,
-                            line: self.line,
-                        })
-                    } else {
-                        tokens.push(Token {
-                            tty: TokenType::Equal,
-                            value: Value::None,
-                            line: self.line,
-                        })
+                let number = self.source[start..self.current].to_owned();
+                match number.parse::<u32>() {
+                    Ok(version)
+                        if (OLDEST_LANGUAGE_VERSION..=LATEST_LANGUAGE_VERSION)
+                            .contains(&version) =>
+                    {
+                        self.language_version = version;
+                        self.version_pragma = true;
+                        self.push_trivia(TriviaKind::Directive, self.span());
+                        self.directive_line_end(&name)?;
                     }
+                    _ => {
+                        return Err(self.error(
+                            ScannerErrorType::UnsupportedVersion,
+                            &[
+                                &number,
+                                &self.line,
+                                &OLDEST_LANGUAGE_VERSION,
+                                &LATEST_LANGUAGE_VERSION,
+                            ],
+                        ))
+                    }
+                }
+            }
+            "if" => {
+                let flag = self.directive_flag();
+                self.push_trivia(TriviaKind::Directive, self.span());
+                // Open the condition even if the line is malformed, so the
+                // matching `#end` is not reported as well.
+                self.conditions.push(Condition {
+                    line: self.line,
+                    span: self.span(),
+                    in_else: false,
+                });
+                let flag = flag?;
+                let line_end = self.directive_line_end(&name);
+                if !self.flags.contains(&flag) {
+                    line_end.and(self.skip_inactive())?;
+                } else {
+                    line_end?;
+                }
+            }
+            "else" => match self.conditions.last_mut() {
+                Some(condition) if !condition.in_else => {
+                    condition.in_else = true;
+                    self.push_trivia(TriviaKind::Directive, self.span());
+                    let line_end = self.directive_line_end(&name);
+                    line_end.and(self.skip_inactive())?;
+                }
+                _ => {
+                    return Err(
+                        self.error(ScannerErrorType::UnmatchedDirective, &[&name, &self.line])
+                    )
                 }
-                '!' => {
-                    if self.next() == '=' {
-                        tokens.push(Token {
-                            tty: TokenType::NotEqual,
-                            value: Value::None,
-                            line: self.line,
-                        })
-                    } else {
-                        tokens.push(Token {
-                            tty: TokenType::Not,
-                            value: Value::None,
-                            line: self.line,
-                        })
+            },
+            "end" => {
+                if self.conditions.pop().is_none() {
+                    return Err(
+                        self.error(ScannerErrorType::UnmatchedDirective, &[&name, &self.line])
+                    );
+                }
+                self.push_trivia(TriviaKind::Directive, self.span());
+                self.directive_line_end(&name)?;
+            }
+            _ => {
+                let error = self.error(ScannerErrorType::UnknownDirective, &[&name, &self.line]);
+                let known = if name.starts_with("if") {
+                    "if"
+                } else if name.starts_with("el") {
+                    "else"
+                } else if name.starts_with("end") {
+                    "end"
+                } else if name.starts_with("ver") {
+                    "version"
+                } else {
+                    return Err(error);
+                };
+                let fix = self.suggest("V0005-rename", &[&known], &format!("#{}", known));
+                return Err(error.with_suggestion(fix));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether only whitespace precedes the current lexeme on its line.
+    fn at_line_start(&self) -> bool {
+        let before = &self.source[..self.start];
+        let line = &before[before.rfind('\n').map_or(0, |i| i + 1)..];
+        line.chars().all(|c| matches!(c, ' ' | '\t' | '\r'))
+    }
+
+    /// Checks that nothing but whitespace or a `//` comment follows a
+    /// directive on its line. Anything else is skipped, so it is neither
+    /// scanned as code nor mistaken for another directive.
+    fn directive_line_end(&mut self, name: &str) -> Result<(), ScannerError> {
+        let rest = &self.source[self.current..];
+        let line = &rest[..rest.find('\n').unwrap_or(rest.len())];
+        let content = line.trim_start_matches([' ', '\t', '\r']);
+        if content.is_empty() || content.starts_with("//") {
+            return Ok(());
+        }
+        let line_end = self.current + line.len();
+        let content_start = line_end - content.len();
+        let error = self.error(ScannerErrorType::MisplacedDirective, &[&name, &self.line]);
+        let error = error.with_suggestion(self.line_break_at(content_start));
+        let skipped = Span {
+            start: self.current as u32,
+            end: line_end as u32,
+        };
+        self.push_trivia(TriviaKind::Skipped, skipped);
+        self.current = line_end;
+        Err(error)
+    }
+
+    fn line_break_at(&self, offset: usize) -> Suggestion {
+        Suggestion {
+            msg: self.catalog.format("V0015-newline", &[]),
+            span: Span {
+                start: offset as u32,
+                end: offset as u32,
+            },
+            replacement: "\n".to_owned(),
+        }
+    }
+
+    fn directive_name(&mut self) -> String {
+        let start = self.current;
+        while self.peek().is_alphanumeric() || self.peek() == '_' {
+            self.advance();
+        }
+        self.source[start..self.current].to_owned()
+    }
+
+    fn directive_flag(&mut self) -> Result<String, ScannerError> {
+        while matches!(self.peek(), ' ' | '\t') {
+            self.advance();
+        }
+        let flag = self.directive_name();
+        if flag.is_empty() {
+            return Err(self.error(ScannerErrorType::MissingFlag, &[&self.line]));
+        }
+        Ok(flag)
+    }
+
+    /// Skips the rest of a branch that is not taken, stopping after the
+    /// `#else` or `#end` that closes it. Nested `#if`s inside the skipped
+    /// region are only counted, never evaluated.
+    fn skip_inactive(&mut self) -> Result<(), ScannerError> {
+        let skipped_from = self.current as u32;
+        let mut depth = 0;
+        loop {
+            while !self.is_at_end() && self.peek() != '\n' {
+                self.advance();
+            }
+            if self.is_at_end() {
+                let skipped = Span {
+                    start: skipped_from,
+                    end: self.current as u32,
+                };
+                self.push_trivia(TriviaKind::Skipped, skipped);
+                return Err(self
+                    .unclosed_condition()
+                    .expect("skipping outside of a condition"));
+            }
+            self.advance();
+            self.line += 1;
+            while matches!(self.peek(), ' ' | '\t' | '\r') {
+                self.advance();
+            }
+            if self.peek() != '#' {
+                continue;
+            }
+            self.start = self.current;
+            self.advance();
+            let name = self.directive_name();
+            let closes = match name.as_str() {
+                "end" => depth == 0,
+                "else" => depth == 0 && !self.conditions.last().is_some_and(|c| c.in_else),
+                _ => false,
+            };
+            if closes {
+                let skipped = Span {
+                    start: skipped_from,
+                    end: self.start as u32,
+                };
+                self.push_trivia(TriviaKind::Skipped, skipped);
+                self.push_trivia(TriviaKind::Directive, self.span());
+            }
+            match name.as_str() {
+                "if" => depth += 1,
+                "end" if depth > 0 => depth -= 1,
+                "end" => {
+                    self.conditions.pop();
+                    return self.directive_line_end(&name);
+                }
+                "else" if depth == 0 => {
+                    let condition = self
+                        .conditions
+                        .last_mut()
+                        .expect("skipping outside of a condition");
+                    if condition.in_else {
+                        return Err(
+                            self.error(ScannerErrorType::UnmatchedDirective, &[&name, &self.line])
+                        );
                     }
+                    condition.in_else = true;
+                    return self.directive_line_end(&name);
                 }
-                '"' => tokens.push(self.tokenize_string()?),
+                _ => {}
+            }
+        }
+    }
+
+    /// Pops the innermost open `#if` and reports it as never closed.
+    fn unclosed_condition(&mut self) -> Option<ScannerError> {
+        let condition = self.conditions.pop()?;
+        Some(self.error_at(
+            condition.span,
+            ScannerErrorType::UnterminatedDirective,
+            &[&condition.line],
+        ))
+    }
+
+    /// Skips a `/* ... */` comment, which may contain nested block comments.
+    fn block_comment(&mut self) -> Result<(), ScannerError> {
+        let start_line = self.line;
+        let mut depth = 1;
+        let mut hidden = None;
+        while depth > 0 {
+            if hidden.is_none() {
+                hidden = self.hidden_directive();
+            }
+            if self.is_at_end() {
+                let opening = Span {
+                    start: self.start as u32,
+                    end: self.start as u32 + 2,
+                };
+                let text = self.source.trim_end();
+                // Close every open level. After a `/` the first `*/` would
+                // open another comment instead, so keep them apart.
+                let separator = if text.ends_with('/') { " " } else { "" };
+                let end = text.len() as u32;
+                let fix = Suggestion {
+                    msg: self.catalog.format("V0014-close", &[]),
+                    span: Span { start: end, end },
+                    replacement: format!("{}{}", separator, "*/".repeat(depth)),
+                };
+                return Err(self
+                    .error_at(
+                        opening,
+                        ScannerErrorType::UnterminatedComment,
+                        &[&start_line],
+                    )
+                    .with_suggestion(fix));
+            }
+            match self.advance() {
                 '\n' => self.line += 1,
-                ' ' => (),
+                '/' if self.match_next('*') => depth += 1,
+                '*' if self.match_next('/') => depth -= 1,
                 _ => {}
             }
         }
+        self.push_trivia(TriviaKind::BlockComment, self.span());
+        hidden.map_or(Ok(()), Err)
+    }
+
+    /// Inside an `#if`, a line of a string or block comment that starts with
+    /// `#if`, `#else` or `#end`. [`skip_inactive`](Self::skip_inactive) does
+    /// not scan literals and would take such a line for a directive, so it is
+    /// rejected in taken branches as well and a file scans the same way
+    /// whichever branch is taken. Checked just after a newline.
+    fn hidden_directive(&self) -> Option<ScannerError> {
+        if self.conditions.is_empty() || !self.source[..self.current].ends_with('\n') {
+            return None;
+        }
+        let rest = &self.source[self.current..];
+        let content = rest.trim_start_matches([' ', '\t', '\r']);
+        let name = content.strip_prefix('#')?;
+        let name = &name[..name
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(name.len())];
+        if !matches!(name, "if" | "else" | "end") {
+            return None;
+        }
+        let start = self.current + rest.len() - content.len();
+        let span = Span {
+            start: start as u32,
+            end: (start + 1 + name.len()) as u32,
+        };
+        Some(self.error_at(
+            span,
+            ScannerErrorType::HiddenDirective,
+            &[&name, &self.line],
+        ))
+    }
+
+    /// Enters a bracket, failing once the nesting limit is exceeded.
+    fn open(&mut self) -> Result<(), ScannerError> {
+        self.depth += 1;
+        if self.depth > self.limits.max_nesting {
+            return Err(self.error(
+                ScannerErrorType::NestingTooDeep,
+                &[&self.limits.max_nesting, &self.line],
+            ));
+        }
+        Ok(())
+    }
+
+    /// Leaves a bracket. Unbalanced closers are left for the parser.
+    fn close(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+    }
+
+    /// Rejects a host-supplied default version this crate does not implement.
+    fn check_language_version(&self) -> Result<(), ScannerError> {
+        let version = self.language_version;
+        if (OLDEST_LANGUAGE_VERSION..=LATEST_LANGUAGE_VERSION).contains(&version) {
+            return Ok(());
+        }
+        Err(ScannerError {
+            msg: self.catalog.format(
+                "V0008-host",
+                &[&version, &OLDEST_LANGUAGE_VERSION, &LATEST_LANGUAGE_VERSION],
+            ),
+            tty: ScannerErrorType::UnsupportedVersion,
+            file: self.file,
+            span: Span { start: 0, end: 0 },
+            suggestion: None,
+        })
+    }
+
+    fn check_token_count(&self, tokens: &[Token]) -> Result<(), ScannerError> {
+        match tokens.last() {
+            Some(last) if tokens.len() > self.limits.max_tokens => Err(self.error_at(
+                last.span,
+                ScannerErrorType::TooManyTokens,
+                &[&self.limits.max_tokens, &last.line],
+            )),
+            _ => Ok(()),
+        }
+    }
 
-        Ok(tokens)
+    /// Pops the innermost open `${` and reports its string as unterminated.
+    fn unclosed_interpolation(&mut self) -> Option<ScannerError> {
+        let interpolation = self.interpolations.pop()?;
+        Some(self.error_at(
+            interpolation.span,
+            ScannerErrorType::UnterminatedString,
+            &[&interpolation.line],
+        ))
     }
 
-    pub fn tokenize_string(&mut self) -> Result<Token, VeonError> {
-        while self.next() != '"' {}
-        let text = self.source.substring(self.start + 1, self.current - 1);
-        Ok(Token {
-            tty: TokenType::String,
-            value: Value::String(text.to_owned()),
-            line: self.line,
+    pub fn tokenize_string(&mut self) -> Result<Token, ScannerError> {
+        self.string_segment(self.line)
+    }
+
+    /// Scans string text up to the closing `"`, producing a `String` token,
+    /// or up to a `${`, producing an `Interpolation` token. After an
+    /// interpolation the embedded expression is scanned as ordinary tokens,
+    /// and the string resumes at the `}` that closes it, so
+    /// `"a ${b} c"` becomes `Interpolation("a ")`, `b`, `String(" c")`.
+    fn string_segment(&mut self, start_line: usize) -> Result<Token, ScannerError> {
+        let mut text = String::new();
+        // The first error inside the literal, reported once it is closed.
+        let mut deferred = None;
+        let mut too_long = false;
+        loop {
+            if deferred.is_none() {
+                deferred = self.hidden_directive();
+            }
+            if text.len() > self.limits.max_string_length {
+                // Stop buffering, but scan on to the end of the literal.
+                too_long = true;
+                text.clear();
+            }
+            if self.is_at_end() {
+                let error = self.error(ScannerErrorType::UnterminatedString, &[&start_line]);
+                if !self.interpolations.is_empty() {
+                    // The strings around this `${` cannot be closed either;
+                    // report this one only. A quote straight inside a lone
+                    // `${` was most likely meant to end it, as in `"${x"`.
+                    let lone = self.interpolations.len() == 1
+                        && self.interpolations[0].braces == 0
+                        && self.source[self.start..].starts_with('"');
+                    self.interpolations.clear();
+                    if !lone {
+                        return Err(error);
+                    }
+                    let fix = Suggestion {
+                        msg: self.catalog.format("V0002-interpolation", &[]),
+                        span: Span {
+                            start: self.start as u32,
+                            end: self.start as u32,
+                        },
+                        replacement: "}".to_owned(),
+                    };
+                    return Err(error.with_suggestion(fix));
+                }
+                let line_end = self.source[self.start..]
+                    .find('\n')
+                    .map_or(self.current, |i| self.start + i);
+                let line_end = self.source[..line_end].trim_end().len();
+                let backslashes = self.source[self.start..line_end]
+                    .chars()
+                    .rev()
+                    .take_while(|&c| c == '\\')
+                    .count();
+                if backslashes % 2 == 1 {
+                    // A quote here would be escaped, and no single insertion
+                    // keeps the text as written.
+                    return Err(error);
+                }
+                let fix = Suggestion {
+                    msg: self.catalog.format("V0002-close", &[]),
+                    span: Span {
+                        start: line_end as u32,
+                        end: line_end as u32,
+                    },
+                    replacement: "\"".to_owned(),
+                };
+                return Err(error.with_suggestion(fix));
+            }
+            match self.advance() {
+                '"' => break,
+                '$' if self.match_next('{') => {
+                    self.interpolations.push(Interpolation {
+                        line: start_line,
+                        span: self.span(),
+                        braces: 0,
+                    });
+                    self.open()?;
+                    if let Some(error) = self.segment_error(deferred, too_long, start_line) {
+                        return Err(error);
+                    }
+                    return Ok(self.make_literal(TokenType::Interpolation, Value::String(text)));
+                }
+                '\\' => {
+                    let escape_start = self.current - 1;
+                    // A backslash before a newline advances the line count.
+                    let escape_line = self.line;
+                    match self.escape() {
+                        Some(c) => text.push(c),
+                        None => {
+                            // Keep going so the rest of the string is not
+                            // rescanned as code, then report the first one.
+                            deferred.get_or_insert_with(|| {
+                                self.invalid_escape(escape_start, escape_line)
+                            });
+                        }
+                    }
+                }
+                '\n' => {
+                    self.line += 1;
+                    text.push('\n');
+                }
+                c => text.push(c),
+            }
+        }
+        if let Some(error) = self.segment_error(deferred, too_long, start_line) {
+            return Err(error);
+        }
+        Ok(self.make_literal(TokenType::String, Value::String(text)))
+    }
+
+    fn segment_error(
+        &self,
+        deferred: Option<ScannerError>,
+        too_long: bool,
+        start_line: usize,
+    ) -> Option<ScannerError> {
+        deferred.or_else(|| {
+            too_long.then(|| {
+                self.error(
+                    ScannerErrorType::StringTooLong,
+                    &[&start_line, &self.limits.max_string_length],
+                )
+            })
         })
     }
+
+    /// Decodes the escape after a backslash: `\n`, `\t`, `\r`, `\0`, `\"`,
+    /// `\$`, `\\` or `\u{XXXX}`. Returns `None` if it is not a valid escape.
+    fn escape(&mut self) -> Option<char> {
+        if self.is_at_end() {
+            return None;
+        }
+        match self.advance() {
+            'n' => Some('\n'),
+            't' => Some('\t'),
+            'r' => Some('\r'),
+            '0' => Some('\0'),
+            '"' => Some('"'),
+            '$' => Some('$'),
+            '\\' => Some('\\'),
+            'u' => {
+                if !self.match_next('{') {
+                    return None;
+                }
+                let digits_start = self.current;
+                while self.peek().is_ascii_hexdigit() {
+                    self.advance();
+                }
+                let digits_end = self.current;
+                if !(1..=6).contains(&(digits_end - digits_start)) || !self.match_next('}') {
+                    return None;
+                }
+                u32::from_str_radix(&self.source[digits_start..digits_end], 16)
+                    .ok()
+                    .and_then(char::from_u32)
+            }
+            '\n' => {
+                self.line += 1;
+                None
+            }
+            _ => None,
+        }
+    }
+
+    fn invalid_escape(&self, escape_start: usize, line: usize) -> ScannerError {
+        let span = Span {
+            start: escape_start as u32,
+            end: self.current as u32,
+        };
+        let escape = &self.source[span.range()];
+        let error = self.error_at(span, ScannerErrorType::InvalidEscape, &[&escape, &line]);
+        if escape.starts_with("\\u") {
+            return error;
+        }
+        error.with_suggestion(Suggestion {
+            msg: self.catalog.format("V0010-backslash", &[]),
+            span: Span {
+                start: span.start,
+                end: span.start + 1,
+            },
+            replacement: "\\\\".to_owned(),
+        })
+    }
+
+    /// Scans an integer, or a float when the digits are followed by `.` and
+    /// at least one more digit. `1.foo` stays a number followed by a dot.
+    pub fn tokenize_number(&mut self) -> Result<Token, ScannerError> {
+        while self.peek().is_ascii_digit() {
+            self.advance();
+        }
+        if self.peek() == '.' && self.peek_next().is_ascii_digit() {
+            self.advance();
+            while self.peek().is_ascii_digit() {
+                self.advance();
+            }
+            let text = self.lexeme();
+            let number = text
+                .parse::<f64>()
+                .ok()
+                .filter(|number| number.is_finite())
+                .ok_or_else(|| self.error(ScannerErrorType::InvalidNumber, &[&text, &self.line]))?;
+            return Ok(self.make_literal(TokenType::Number, Value::Float(number)));
+        }
+        let text = self.lexeme();
+        let number = text
+            .parse::<isize>()
+            .map_err(|_| self.error(ScannerErrorType::InvalidNumber, &[&text, &self.line]))?;
+        Ok(self.make_literal(TokenType::Number, Value::Number(number)))
+    }
+
+    /// Scans an identifier and resolves it against [`KEYWORDS`] in a single
+    /// lookup. Identifier names are read back from the span, never copied.
+    pub fn tokenize_identifier(&mut self) -> Token {
+        while self.peek().is_alphanumeric() || self.peek() == '_' {
+            self.advance();
+        }
+        match KEYWORDS.get(self.lexeme()).copied() {
+            Some(TokenType::Boolean) => {
+                let value = Value::Boolean(self.lexeme() == "true");
+                self.make_literal(TokenType::Boolean, value)
+            }
+            Some(tty) => self.make_token(tty),
+            None => self.make_token(TokenType::Identifier),
+        }
+    }
+
+    /// Records trivia when retaining it, merging runs of the same kind.
+    fn push_trivia(&mut self, kind: TriviaKind, span: Span) {
+        if !self.retain_trivia || span.start == span.end {
+            return;
+        }
+        match self.trivia.last_mut() {
+            Some(last) if last.kind == kind && last.span.end == span.start => {
+                last.span.end = span.end
+            }
+            _ => self.trivia.push(Trivia { kind, span }),
+        }
+    }
+
+    fn make_token(&self, tty: TokenType) -> Token {
+        Token {
+            tty,
+            span: self.span(),
+            line: self.line as u32,
+            literal: None,
+        }
+    }
+
+    fn make_literal(&mut self, tty: TokenType, value: Value) -> Token {
+        self.literals.push(value);
+        Token {
+            literal: Some(self.literals.len() as u32 - 1),
+            ..self.make_token(tty)
+        }
+    }
+
+    fn error(&self, tty: ScannerErrorType, args: &[&dyn Display]) -> ScannerError {
+        self.error_at(self.span(), tty, args)
+    }
+
+    fn error_at(&self, span: Span, tty: ScannerErrorType, args: &[&dyn Display]) -> ScannerError {
+        ScannerError {
+            msg: self.catalog.format(tty.code(), args),
+            tty,
+            file: self.file,
+            span,
+            suggestion: None,
+        }
+    }
+
+    /// A suggestion replacing the current lexeme with `replacement`.
+    fn suggest(&self, key: &str, args: &[&dyn Display], replacement: &str) -> Suggestion {
+        Suggestion {
+            msg: self.catalog.format(key, args),
+            span: self.span(),
+            replacement: replacement.to_owned(),
+        }
+    }
+
+    fn span(&self) -> Span {
+        Span {
+            start: self.start as u32,
+            end: self.current as u32,
+        }
+    }
+
+    fn lexeme(&self) -> &str {
+        &self.source[self.start..self.current]
+    }
+
     pub fn is_at_end(&self) -> bool {
         self.source.len() == self.current
     }
 
-    pub fn next(&mut self) -> char {
-        let c = self.source.chars().collect::<Vec<_>>()[self.current];
-        self.current += 1;
+    pub fn advance(&mut self) -> char {
+        let c = self.source[self.current..]
+            .chars()
+            .next()
+            .expect("advance past end of source");
+        self.current += c.len_utf8();
         c
     }
+
+    pub fn peek(&self) -> char {
+        self.source[self.current..].chars().next().unwrap_or('\0')
+    }
+
+    pub fn peek_next(&self) -> char {
+        let mut chars = self.source[self.current..].chars();
+        chars.next();
+        chars.next().unwrap_or('\0')
+    }
+
+    fn match_next(&mut self, expected: char) -> bool {
+        if self.peek() != expected {
+            return false;
+        }
+        self.current += expected.len_utf8();
+        true
+    }
 }
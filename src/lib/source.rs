@@ -0,0 +1,108 @@
+use crate::{
+    error::{Suggestion, VeonError},
+    token::Span,
+};
+
+/// Identifies a file registered in a [`SourceMap`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct FileId(pub u32);
+
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    pub name: String,
+    pub source: String,
+    line_starts: Vec<usize>,
+}
+
+/// Every source file taking part in a run, so diagnostics from any phase can
+/// be reported as `file:line:col` against the right text.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, name: impl Into<String>, source: impl Into<String>) -> FileId {
+        let source = source.into();
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        self.files.push(SourceFile {
+            name: name.into(),
+            source,
+            line_starts,
+        });
+        FileId(self.files.len() as u32 - 1)
+    }
+
+    pub fn file(&self, id: FileId) -> &SourceFile {
+        &self.files[id.0 as usize]
+    }
+
+    /// 1-based line and column (in characters) of a byte offset.
+    pub fn location(&self, id: FileId, offset: usize) -> (usize, usize) {
+        let file = self.file(id);
+        let line = file.line_starts.partition_point(|&start| start <= offset) - 1;
+        let line_start = file.line_starts[line];
+        let column = file.source[line_start..offset].chars().count() + 1;
+        (line + 1, column)
+    }
+
+    /// Formats an error as `file:line:col: error[code]: message`, followed by
+    /// the offending source line and a caret under the span when the error
+    /// carries a location, and the fixed line when it carries a suggestion.
+    pub fn render(&self, error: &VeonError) -> String {
+        match error {
+            VeonError::ScannerError(e) => {
+                let mut out = self.render_at(e.file, e.span, e.tty.code(), &e.msg);
+                if let Some(suggestion) = &e.suggestion {
+                    out.push('\n');
+                    out.push_str(&self.render_suggestion(e.file, suggestion));
+                }
+                out
+            }
+            VeonError::ParserError(e) => format!("error[{}]: {}", e.tty.code(), e.msg),
+            VeonError::EvaluationError(e) => format!("error[{}]: {}", e.tty.code(), e.msg),
+        }
+    }
+
+    fn render_at(&self, id: FileId, span: Span, code: &str, msg: &str) -> String {
+        let file = self.file(id);
+        let (line, column) = self.location(id, span.start as usize);
+        let text = file.source[file.line_starts[line - 1]..]
+            .lines()
+            .next()
+            .unwrap_or("");
+        let width = file.source[span.range()]
+            .lines()
+            .next()
+            .map_or(1, |first| first.chars().count().max(1));
+        format!(
+            "{}:{}:{}: error[{}]: {}\n {:>4} | {}\n      | {}{}",
+            file.name,
+            line,
+            column,
+            code,
+            msg,
+            line,
+            text,
+            " ".repeat(column - 1),
+            "^".repeat(width)
+        )
+    }
+
+    fn render_suggestion(&self, id: FileId, suggestion: &Suggestion) -> String {
+        let file = self.file(id);
+        let (line, _) = self.location(id, suggestion.span.start as usize);
+        let fixed = suggestion.apply(&file.source);
+        let text = fixed[file.line_starts[line - 1]..]
+            .lines()
+            .next()
+            .unwrap_or("");
+        format!("help: {}\n {:>4} | {}", suggestion.msg, line, text)
+    }
+}
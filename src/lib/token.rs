@@ -1,17 +1,35 @@
-#[derive(Debug, Clone)]
+use crate::source::FileId;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TokenType {
     Fun,
     Let,
+    And,
+    Or,
     Boolean,
     Number,
     String,
+    /// A string segment followed by an embedded `${...}` expression.
+    Interpolation,
+    Identifier,
     // Array,
     // Object,
+    LeftParen,
+    RightParen,
+    LeftBrace,
+    RightBrace,
+    LeftBracket,
+    RightBracket,
+    Comma,
+    Dot,
+    Ellipsis,
+    Semicolon,
     Plus,
     Minus,
     Star,
     Slash,
     QuestionMark,
+    QuestionDot,
     Greater,
     GreaterEqual,
     Less,
@@ -28,18 +46,116 @@ pub enum TokenType {
 pub enum Value {
     String(String),
     Number(isize),
+    Float(f64),
     Boolean(bool),
     None,
 }
-#[derive(Debug, Clone)]
+/// Byte range of a token in the scanned source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: u32,
+    pub end: u32,
+}
+
+impl Span {
+    pub fn range(&self) -> std::ops::Range<usize> {
+        self.start as usize..self.end as usize
+    }
+}
 
+/// A token is plain data: punctuation and identifiers are described entirely
+/// by their span, and literals point into [`Tokens::literals`].
+#[derive(Debug, Clone, Copy)]
 pub struct Token {
     pub tty: TokenType,
-    pub value: Value,
-    pub line: usize,
+    pub span: Span,
+    pub line: u32,
+    pub literal: Option<u32>,
+}
+
+impl Token {
+    /// The source text this token was scanned from.
+    pub fn lexeme<'a>(&self, source: &'a str) -> &'a str {
+        &source[self.span.range()]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriviaKind {
+    Whitespace,
+    LineComment,
+    /// A `/* ... */` comment, including any comments nested inside it.
+    BlockComment,
+    /// An `#if`, `#else` or `#end` line.
+    Directive,
+    /// Source inside a conditional branch that was not taken.
+    Skipped,
 }
-// def, fn, fun, func, function, define,
-// define, let, var
+
+/// Source text between tokens, kept when scanning with
+/// [`Scanner::with_trivia`](crate::scanner::Scanner::with_trivia).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub span: Span,
+}
+
+/// Output of the scanner: the token vector plus the literal table it indexes,
+/// and the trivia between tokens when requested.
+#[derive(Debug, Clone)]
+pub struct Tokens {
+    pub file: FileId,
+    pub tokens: Vec<Token>,
+    pub literals: Vec<Value>,
+    pub trivia: Vec<Trivia>,
+    /// Selected by a `#version` pragma, or the scanner's default.
+    pub language_version: u32,
+}
+
+impl Tokens {
+    pub fn literal(&self, token: &Token) -> Option<&Value> {
+        token.literal.map(|index| &self.literals[index as usize])
+    }
+
+    /// Trivia between the previous token and `tokens[index]`. Passing
+    /// `tokens.len()` gives the trivia after the last token.
+    pub fn leading_trivia(&self, index: usize) -> &[Trivia] {
+        let from = match index {
+            0 => 0,
+            _ => self.tokens[index - 1].span.end,
+        };
+        let to = self
+            .tokens
+            .get(index)
+            .map_or(u32::MAX, |token| token.span.start);
+        let lo = self
+            .trivia
+            .partition_point(|trivia| trivia.span.start < from);
+        let hi = self.trivia.partition_point(|trivia| trivia.span.start < to);
+        &self.trivia[lo..hi]
+    }
+}
+
+/// Reserved words, including the accepted aliases for `fun` and `let`.
+///
+/// None of these can be used as an identifier: the declaration words `fun`
+/// (`def`, `fn`, `func`, `function`, `define`) and `let` (`var`), the logical
+/// operators `and` and `or`, and the literals `true`, `false` and `null`.
+pub static KEYWORDS: phf::Map<&'static str, TokenType> = phf::phf_map! {
+    "def" => TokenType::Fun,
+    "fn" => TokenType::Fun,
+    "fun" => TokenType::Fun,
+    "func" => TokenType::Fun,
+    "function" => TokenType::Fun,
+    "define" => TokenType::Fun,
+    "let" => TokenType::Let,
+    "var" => TokenType::Let,
+    "and" => TokenType::And,
+    "or" => TokenType::Or,
+    "true" => TokenType::Boolean,
+    "false" => TokenType::Boolean,
+    "null" => TokenType::Null,
+};
 
 // 1 ?? "" = "1"
 // "10 " ?? "10" ?? " HEY" "10 10 HEY"
@@ -1,8 +1,97 @@
 fn main() -> Result<(), veonep::error::VeonError> {
-    let source = r#"
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("bench") => {
+            let config = veonep::bench::BenchConfig::default();
+            for report in veonep::bench::run(config)? {
+                println!("{}", report);
+            }
+            println!("{}", veonep::bench::run_scanner(config, 100)?);
+        }
+        Some("explain") => match args.get(1).and_then(|code| veonep::explain::explain(code)) {
+            Some(explanation) => {
+                println!(
+                    "{}: {}\n\n{}",
+                    explanation.code, explanation.title, explanation.text
+                )
+            }
+            None => {
+                eprintln!("usage: veonep explain <code>\n\nknown codes:");
+                for explanation in veonep::explain::EXPLANATIONS {
+                    eprintln!("  {}  {}", explanation.code, explanation.title);
+                }
+                std::process::exit(1);
+            }
+        },
+        Some("minify") => {
+            if args.len() == 1 {
+                eprintln!("usage: veonep minify <file>...");
+                std::process::exit(1);
+            }
+            let mut sources = veonep::source::SourceMap::new();
+            for path in &args[1..] {
+                let source = match std::fs::read_to_string(path) {
+                    Ok(source) => source,
+                    Err(e) => {
+                        eprintln!("{}: {}", path, e);
+                        std::process::exit(1);
+                    }
+                };
+                let file = sources.add(path.as_str(), source.as_str());
+                let scanner = veonep::scanner::Scanner::new(source).with_file(file);
+                match veonep::minify::minify(scanner) {
+                    Ok(minified) => println!("{}", minified),
+                    Err(error) => {
+                        eprintln!("{}", sources.render(&error));
+                        std::process::exit(1);
+                    }
+                }
+            }
+        }
+        // Anything that does not look like a path is a mistyped subcommand.
+        Some(command)
+            if !command.contains(['.', '/', '\\']) && !std::path::Path::new(command).exists() =>
+        {
+            eprintln!(
+                "unknown command '{}'\n\nusage: veonep <file>...\n       veonep bench\n       veonep explain <code>\n       veonep minify <file>...",
+                command
+            );
+            std::process::exit(1);
+        }
+        Some(_) => {
+            let mut sources = veonep::source::SourceMap::new();
+            let mut failed = false;
+            for path in &args {
+                let source = match std::fs::read_to_string(path) {
+                    Ok(source) => source,
+                    Err(e) => {
+                        eprintln!("{}: {}", path, e);
+                        std::process::exit(1);
+                    }
+                };
+                let file = sources.add(path.as_str(), source.as_str());
+                let (tokens, errors) = veonep::scanner::Scanner::new(source)
+                    .with_file(file)
+                    .tokenize_recovering();
+                if errors.is_empty() {
+                    println!("{:?}", tokens);
+                }
+                for error in errors {
+                    eprintln!("{}\n", sources.render(&error.into()));
+                    failed = true;
+                }
+            }
+            if failed {
+                std::process::exit(1);
+            }
+        }
+        None => {
+            let source = r#"
     "Hey" + "WOW"
     "#;
-    let mut tokens = veonep::scanner::Scanner::new(source.to_owned());
-    println!("{:?}", tokens.tokenize()?);
+            let mut tokens = veonep::scanner::Scanner::new(source.to_owned());
+            println!("{:?}", tokens.tokenize()?);
+        }
+    }
     Ok(())
 }
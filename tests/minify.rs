@@ -0,0 +1,59 @@
+use veonep::{minify::minify, scanner::Scanner};
+
+fn kinds_and_lexemes(source: &str) -> Vec<String> {
+    let tokens = Scanner::new(source.to_owned()).tokenize().unwrap();
+    tokens
+        .tokens
+        .iter()
+        .map(|t| format!("{:?} {}", t.tty, t.lexeme(source)))
+        .collect()
+}
+
+#[test]
+fn minify_strips_comments_and_whitespace() {
+    let source = "// greet\nlet  name = \"veon\" ;  // trailing\nprint name + 1 .abs;\n";
+    let minified = minify(Scanner::new(source.to_owned())).unwrap();
+    assert_eq!(minified, "let name=\"veon\";print name+1 .abs;");
+}
+
+#[test]
+fn minify_preserves_the_token_stream_of_the_spec_corpus() {
+    let mut paths: Vec<_> = std::fs::read_dir("tests/spec")
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "veon"))
+        .filter(|path| path.with_extension("tokens").exists())
+        .collect();
+    paths.sort();
+    for path in paths {
+        let source = std::fs::read_to_string(&path).unwrap();
+        let minified = minify(Scanner::new(source.clone())).unwrap();
+        assert_eq!(
+            kinds_and_lexemes(&minified),
+            kinds_and_lexemes(&source),
+            "{}",
+            path.display()
+        );
+        let flagged = |text: &str| {
+            Scanner::new(text.to_owned())
+                .with_flags(["DEBUG", "VERBOSE"])
+                .tokenize()
+                .unwrap()
+                .tokens
+                .len()
+        };
+        assert_eq!(flagged(&minified), flagged(&source), "{}", path.display());
+    }
+}
+
+#[test]
+fn minify_keeps_skipped_lines_where_they_were() {
+    let source = "#if B // why\nlet  a = 1;\n#else\nlet b = 2; // fallback\n  #end\n";
+    let minified = minify(Scanner::new(source.to_owned())).unwrap();
+    assert_eq!(minified, "#if B // why\nlet  a = 1;\n#else\nlet b=2;\n#end");
+    let minified = minify(Scanner::new(source.to_owned()).with_flags(["B"])).unwrap();
+    assert_eq!(
+        minified,
+        "#if B\nlet a=1;\n#else\nlet b = 2; // fallback\n  #end"
+    );
+}
@@ -0,0 +1,281 @@
+use std::sync::Arc;
+
+use veonep::{
+    error::ScannerErrorType,
+    messages::MessageCatalog,
+    scanner::{Limits, Scanner},
+    token::{TokenType, TriviaKind, Value},
+};
+
+#[test]
+fn recovering_scan_reports_every_error() {
+    let source = "let a = 1 @ 2;\nlet b = $;\nlet c = \"open";
+    let (tokens, errors) = Scanner::new(source.to_owned()).tokenize_recovering();
+
+    let kinds: Vec<_> = errors.iter().map(|e| e.tty).collect();
+    assert_eq!(
+        kinds,
+        [
+            ScannerErrorType::InvalidToken,
+            ScannerErrorType::InvalidToken,
+            ScannerErrorType::UnterminatedString,
+        ]
+    );
+    let lets = tokens
+        .tokens
+        .iter()
+        .filter(|token| token.tty == TokenType::Let)
+        .count();
+    assert_eq!(lets, 3);
+}
+
+#[test]
+fn catalog_overrides_message_text() {
+    let mut catalog = MessageCatalog::new();
+    catalog.set("V0002", "String from line {0} never ends");
+    let err = Scanner::new("\"oops".to_owned())
+        .with_catalog(Arc::new(catalog))
+        .tokenize()
+        .unwrap_err();
+    assert_eq!(err.code(), "V0002");
+    assert_eq!(
+        err.to_string(),
+        "Error while scanning: [V0002:UnterminatedString:String from line 1 never ends]"
+    );
+}
+
+#[test]
+fn enabled_flags_select_directive_branches() {
+    let source =
+        "#if DEBUG\ndebug\n#if VERBOSE\nverbose\n#else\nquiet\n#end\n#else\nrelease\n#end\n";
+    let tokens = Scanner::new(source.to_owned())
+        .with_flags(["DEBUG"])
+        .tokenize()
+        .unwrap();
+    let names: Vec<_> = tokens
+        .tokens
+        .iter()
+        .map(|token| (token.lexeme(source), token.line))
+        .collect();
+    assert_eq!(names, [("debug", 2), ("quiet", 6)]);
+}
+
+#[test]
+fn trivia_reproduces_source_exactly() {
+    let source = "// header\nlet a = /* one /* nested */ */ 1; // trailing\n\n#if DEBUG\n  print a;\n#else\nprint \"no\";\n#end\n\t\n";
+    let tokens = Scanner::new(source.to_owned())
+        .with_trivia(true)
+        .tokenize()
+        .unwrap();
+    let mut rebuilt = String::new();
+    for index in 0..=tokens.tokens.len() {
+        for trivia in tokens.leading_trivia(index) {
+            rebuilt.push_str(&source[trivia.span.range()]);
+        }
+        if let Some(token) = tokens.tokens.get(index) {
+            rebuilt.push_str(token.lexeme(source));
+        }
+    }
+    assert_eq!(rebuilt, source);
+    assert_eq!(tokens.leading_trivia(0)[0].kind, TriviaKind::LineComment);
+}
+
+#[test]
+fn decimal_literals_scan_as_floats() {
+    let tokens = Scanner::new("7 / 2.5".to_owned()).tokenize().unwrap();
+    assert!(matches!(
+        tokens.literal(&tokens.tokens[0]),
+        Some(Value::Number(7))
+    ));
+    assert!(matches!(
+        tokens.literal(&tokens.tokens[2]),
+        Some(Value::Float(f)) if *f == 2.5
+    ));
+}
+
+#[test]
+fn string_escapes_are_decoded() {
+    let source = r#""tab\there\nquote\" slash\\ nul\0 snow\u{2603}""#;
+    let tokens = Scanner::new(source.to_owned()).tokenize().unwrap();
+    assert!(matches!(
+        tokens.literal(&tokens.tokens[0]),
+        Some(Value::String(s)) if s == "tab\there\nquote\" slash\\ nul\0 snow\u{2603}"
+    ));
+}
+
+#[test]
+fn invalid_escape_reports_once_and_skips_the_string() {
+    let source = r#"let p = "C:\data\files"; let q = 1;"#;
+    let (tokens, errors) = Scanner::new(source.to_owned()).tokenize_recovering();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].tty, ScannerErrorType::InvalidEscape);
+    assert_eq!(&source[errors[0].span.range()], r"\d");
+    assert_eq!(
+        errors[0].suggestion.as_ref().unwrap().apply(source),
+        r#"let p = "C:\\data\files"; let q = 1;"#
+    );
+    assert_eq!(tokens.tokens.len(), 9);
+}
+
+#[test]
+fn interpolation_splits_string_into_segments() {
+    let source = r#""Hello, ${name}! ${ {} } \${x}""#;
+    let tokens = Scanner::new(source.to_owned()).tokenize().unwrap();
+    let kinds: Vec<_> = tokens.tokens.iter().map(|t| t.tty).collect();
+    assert_eq!(
+        kinds,
+        [
+            TokenType::Interpolation,
+            TokenType::Identifier,
+            TokenType::Interpolation,
+            TokenType::LeftBrace,
+            TokenType::RightBrace,
+            TokenType::String,
+        ]
+    );
+    let segments: Vec<_> = tokens
+        .tokens
+        .iter()
+        .filter_map(|t| match tokens.literal(t) {
+            Some(Value::String(s)) => Some(s.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(segments, ["Hello, ", "! ", " ${x}"]);
+}
+
+#[test]
+fn limits_stop_pathological_sources() {
+    let scan = |source: &str, limits: Limits| {
+        let (_, errors) = Scanner::new(source.to_owned())
+            .with_limits(limits)
+            .tokenize_recovering();
+        errors.iter().map(|e| e.tty).collect::<Vec<_>>()
+    };
+    let limits = Limits {
+        max_tokens: 4,
+        ..Limits::default()
+    };
+    assert_eq!(
+        scan("1 + 2 + 3 + 4", limits),
+        [ScannerErrorType::TooManyTokens]
+    );
+    let limits = Limits {
+        max_string_length: 3,
+        ..Limits::default()
+    };
+    assert_eq!(
+        scan(r#""abc" "abcd" "ab${"abcdef"}c""#, limits),
+        [ScannerErrorType::StringTooLong; 2]
+    );
+    let limits = Limits {
+        max_nesting: 2,
+        ..Limits::default()
+    };
+    assert!(scan("f((a), {b})", limits).is_empty());
+    assert_eq!(
+        scan("((((1)))) ((", limits),
+        [ScannerErrorType::NestingTooDeep]
+    );
+    assert_eq!(
+        scan(r#"("${"${x}"}")"#, limits),
+        [ScannerErrorType::NestingTooDeep]
+    );
+}
+
+#[test]
+fn recovering_scan_stops_at_the_error_limit() {
+    let limits = Limits {
+        max_tokens: 10,
+        max_string_length: 10,
+        max_nesting: 10,
+        max_errors: 10,
+    };
+    let (_, errors) = Scanner::new("@".repeat(100_000))
+        .with_limits(limits)
+        .tokenize_recovering();
+    assert_eq!(errors.len(), 10);
+    let (_, errors) = Scanner::new("#if A\n".repeat(100))
+        .with_flags(["A"])
+        .with_limits(limits)
+        .tokenize_recovering();
+    assert_eq!(errors.len(), 10);
+}
+
+#[test]
+fn unterminated_block_comment_suggestion_closes_every_level() {
+    for source in [
+        "/* a /* b",
+        "/*/",
+        "let a = 1; /* open\n\n",
+        "/* x *",
+        "/* /* a */",
+    ] {
+        let err = Scanner::new(source.to_owned()).tokenize_recovering().1;
+        assert_eq!(err.len(), 1, "{:?}", source);
+        assert_eq!(err[0].tty, ScannerErrorType::UnterminatedComment);
+        let fixed = err[0].suggestion.as_ref().unwrap().apply(source);
+        assert!(
+            Scanner::new(fixed.clone()).tokenize().is_ok(),
+            "{:?} -> {:?}",
+            source,
+            fixed
+        );
+    }
+}
+
+#[test]
+fn quote_suggestions_rescan_cleanly() {
+    for source in [
+        "let s = 'abc';",
+        "let s = 'abc",
+        "let s = 'say \"hi\" ${x} \\d';",
+        "let s = \"abc",
+        "let s = \"abc\\\\",
+        "let s = \"a ${b} c\n",
+        "let s = \"${\"",
+        "let s = \"a ${b + \"c",
+    ] {
+        let (_, errors) = Scanner::new(source.to_owned()).tokenize_recovering();
+        let fixed = errors[0].suggestion.as_ref().unwrap().apply(source);
+        assert!(
+            Scanner::new(fixed.clone()).tokenize().is_ok(),
+            "{:?} -> {:?}",
+            source,
+            fixed
+        );
+    }
+    for (source, keyword) in [
+        ("a&&b", TokenType::And),
+        ("x||y", TokenType::Or),
+        ("a &&c", TokenType::And),
+    ] {
+        let (_, errors) = Scanner::new(source.to_owned()).tokenize_recovering();
+        let fixed = errors[0].suggestion.as_ref().unwrap().apply(source);
+        let tokens = Scanner::new(fixed).tokenize().unwrap();
+        let kinds: Vec<_> = tokens.tokens.iter().map(|token| token.tty).collect();
+        assert_eq!(
+            kinds,
+            [TokenType::Identifier, keyword, TokenType::Identifier]
+        );
+    }
+    let (_, errors) = Scanner::new(r#"let s = "${";"#.to_owned()).tokenize_recovering();
+    assert_eq!(errors.len(), 1);
+    let (_, errors) = Scanner::new(r#"let s = "abc\"#.to_owned()).tokenize_recovering();
+    assert_eq!(errors[0].tty, ScannerErrorType::UnterminatedString);
+    assert!(errors[0].suggestion.is_none());
+}
+
+#[test]
+fn unsupported_host_language_version_is_reported() {
+    let err = Scanner::new("let a = 1;".to_owned())
+        .with_language_version(99)
+        .tokenize()
+        .unwrap_err();
+    assert_eq!(err.code(), "V0008");
+    let (tokens, errors) = Scanner::new("let a = 1;".to_owned())
+        .with_language_version(0)
+        .tokenize_recovering();
+    assert_eq!(errors[0].tty, ScannerErrorType::UnsupportedVersion);
+    assert!(tokens.tokens.is_empty());
+}
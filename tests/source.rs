@@ -0,0 +1,35 @@
+use veonep::{error::VeonError, scanner::Scanner, source::SourceMap};
+
+#[test]
+fn render_points_at_file_line_and_column() {
+    let mut sources = SourceMap::new();
+    sources.add("main.veon", "let a = 1;\n");
+    let file = sources.add("lib.veon", "let a = 1;\nlet é = a @ 2;\n");
+    let err = Scanner::new(sources.file(file).source.clone())
+        .with_file(file)
+        .tokenize()
+        .unwrap_err();
+    assert_eq!(
+        sources.render(&err),
+        "lib.veon:2:11: error[V0001]: Unexpected character '@' on line 2\n    2 | let é = a @ 2;\n      |           ^"
+    );
+}
+
+#[test]
+fn suggestions_apply_and_render() {
+    let mut sources = SourceMap::new();
+    let source = "let s = \"open\nlet t = 1;\n";
+    let file = sources.add("main.veon", source);
+    let err = Scanner::new(source.to_owned())
+        .with_file(file)
+        .tokenize()
+        .unwrap_err();
+    let VeonError::ScannerError(e) = &err else {
+        panic!("expected a scanner error");
+    };
+    let suggestion = e.suggestion.as_ref().unwrap();
+    assert_eq!(suggestion.apply(source), "let s = \"open\"\nlet t = 1;\n");
+    assert!(sources
+        .render(&err)
+        .ends_with("help: close the string\n    1 | let s = \"open\""));
+}
@@ -0,0 +1,110 @@
+//! Runs every program under `tests/spec/`.
+//!
+//! Each `name.veon` is paired with either `name.tokens`, the expected token
+//! dump (one `line kind lexeme` per token), or `name.err`, the expected error
+//! message for programs that must be rejected. Expectations named
+//! `name+FLAG.tokens` or `name+FLAG.err` scan the same program with `FLAG`
+//! set, and several flags can be joined with `+`. Run with `BLESS=1` to
+//! rewrite the expectation files from the current output.
+
+use std::{fs, path::Path};
+
+use veonep::scanner::Scanner;
+
+fn dump(source: &str, flags: &[&str]) -> Result<String, String> {
+    let tokens = Scanner::new(source.to_owned())
+        .with_flags(flags.iter().copied())
+        .tokenize()
+        .map_err(|e| e.to_string())?;
+    Ok(tokens
+        .tokens
+        .iter()
+        .map(|token| {
+            format!(
+                "{} {:?} {}\n",
+                token.line,
+                token.tty,
+                token.lexeme(source).replace('\n', "\\n")
+            )
+        })
+        .collect())
+}
+
+/// The `.tokens` or `.err` file of every variant of `program`, paired with
+/// the flags that variant sets.
+fn variants(program: &Path) -> Vec<(Vec<String>, std::path::PathBuf)> {
+    let name = program.file_stem().unwrap().to_str().unwrap();
+    let mut variants: Vec<_> = fs::read_dir(program.parent().unwrap())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == "tokens" || ext == "err")
+        })
+        .filter_map(|path| {
+            let stem = path.file_stem()?.to_str()?;
+            let flags = if stem == name {
+                vec![]
+            } else {
+                let flags = stem.strip_prefix(name)?.strip_prefix('+')?;
+                flags.split('+').map(str::to_owned).collect()
+            };
+            Some((flags, path))
+        })
+        .collect();
+    variants.sort();
+    variants
+}
+
+#[test]
+fn spec_corpus() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/spec");
+    let mut paths: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "veon"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "no spec programs found in {:?}", dir);
+
+    let bless = std::env::var_os("BLESS").is_some();
+    let mut failures = vec![];
+    for path in &paths {
+        let source = fs::read_to_string(path).unwrap();
+        let variants = variants(path);
+        assert!(
+            !variants.is_empty(),
+            "{:?} has neither a .tokens nor a .err file",
+            path
+        );
+        for (flags, expectation) in variants {
+            let flags: Vec<&str> = flags.iter().map(String::as_str).collect();
+            let actual = dump(&source, &flags);
+            let expected = fs::read_to_string(&expectation).unwrap();
+            let expected = if expectation.extension().is_some_and(|ext| ext == "tokens") {
+                Ok(expected)
+            } else {
+                Err(expected.trim_end().to_owned())
+            };
+            if bless {
+                fs::remove_file(&expectation).unwrap();
+                match &actual {
+                    Ok(dump) => fs::write(expectation.with_extension("tokens"), dump).unwrap(),
+                    Err(msg) => {
+                        fs::write(expectation.with_extension("err"), format!("{}\n", msg)).unwrap()
+                    }
+                }
+                continue;
+            }
+            if expected != actual {
+                failures.push(format!(
+                    "{}\n  expected: {:?}\n  actual:   {:?}",
+                    expectation.display(),
+                    expected,
+                    actual
+                ));
+            }
+        }
+    }
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}